@@ -31,7 +31,10 @@ impl CommandUnderTest {
         self
     }
 
-
+    pub fn env(mut self, key: impl AsRef<std::ffi::OsStr>, value: impl AsRef<std::ffi::OsStr>) -> Self {
+        self.cmd.env(key, value);
+        self
+    }
 
     pub fn assert(mut self) -> assert_cmd::assert::Assert {
         self.cmd.assert()
@@ -45,7 +48,7 @@ impl CommandUnderTest {
 
 // Helper function to copy a directory recursively
 fn copy_dir_all(src: &Path, dst: &Path) -> anyhow::Result<()> {
-    fs::create_dir_all(&dst)?;
+    fs::create_dir_all(dst)?;
     for entry in fs::read_dir(src)? {
         let entry = entry?;
         let file_type = entry.file_type()?;